@@ -1,16 +1,30 @@
 use std::str::FromStr;
+use std::time::Duration;
 
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_client::rpc_client::RpcClient;
+use solana_account_decoder::UiAccountEncoding;
+use solana_address_lookup_table_program::instruction as alt_instruction;
+use solana_client::{
+    pubsub_client::{PubsubClient, PubsubClientSubscription},
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_response::Response,
+};
 use solana_program::pubkey::Pubkey;
 use solana_sdk::{
+    address_lookup_table_account::AddressLookupTableAccount,
+    commitment_config::CommitmentConfig,
     instruction::{AccountMeta, Instruction},
+    message::{v0::Message as MessageV0, VersionedMessage},
     signature::{read_keypair_file, Keypair},
     signer::Signer,
-    transaction::Transaction,
+    transaction::{Transaction, VersionedTransaction},
 };
 #[derive(BorshDeserialize, BorshSerialize, Debug)]
 pub struct UserProfile {
+    /// Assumed to be the signer's pubkey, written first by the on-chain program; unverified
+    /// against the deployed program, which isn't in this repo.
+    pub owner: Pubkey,
     pub data_len: u16,
     pub follows: Vec<Pubkey>,
 }
@@ -34,11 +48,14 @@ pub enum SocialInstruction {
     QueryFollower,
     PostContent { content: String },
     QueryPosts,
+    EditPost { index: u64, content: String },
+    DeletePost { index: u64 },
 }
 
 impl UserProfile {
-    pub fn new() -> Self {
+    pub fn new(owner: Pubkey) -> Self {
         Self {
+            owner,
             data_len: 0,
             follows: Vec::new(),
         }
@@ -74,22 +91,87 @@ impl UserPost {
     pub fn query_posts(&self) -> &Vec<Post> {
         self.posts.as_ref()
     }
+    pub fn edit(&mut self, index: u64, content: String) {
+        if let Some(post) = self.posts.get_mut(index as usize) {
+            post.content = content;
+        }
+    }
+    pub fn delete(&mut self, index: u64) {
+        let index = index as usize;
+        if index < self.posts.len() {
+            self.posts.remove(index);
+            self.post_count = self.posts.len() as u64;
+        }
+    }
 }
 const USER_PROFILE_SEED: &str = "profile";
 const USER_POST_SEED: &str = "post";
+/// Retries for `follow_many`'s versioned transaction while its Address Lookup Table activates.
+const ALT_ACTIVATION_RETRIES: u32 = 5;
+const ALT_ACTIVATION_RETRY_DELAY: Duration = Duration::from_millis(500);
 pub struct SocialClient {
     rpc_client: RpcClient,
+    ws_url: String,
     program_id: Pubkey,
 }
 
+/// Unsubscribes when dropped.
+pub struct AccountSubscription {
+    subscription: Option<PubsubClientSubscription<Response<solana_account_decoder::UiAccount>>>,
+}
+
+impl Drop for AccountSubscription {
+    fn drop(&mut self) {
+        if let Some(subscription) = self.subscription.take() {
+            let _ = subscription.shutdown();
+        }
+    }
+}
+
 impl SocialClient {
     pub fn new(rpc_url: &str, program_id: Pubkey) -> Self {
         let rpc_client = RpcClient::new(rpc_url.to_string());
+        let ws_url = derive_ws_url(rpc_url);
         Self {
             rpc_client,
+            ws_url,
             program_id,
         }
     }
+
+    /// Subscribes to account updates on the PDA derived from `owner`/`seed`, invoking `callback`
+    /// with each deserialized value.
+    pub fn subscribe_account<T, F>(
+        &self,
+        owner: &Pubkey,
+        seed: &str,
+        mut callback: F,
+    ) -> Result<AccountSubscription, Box<dyn std::error::Error>>
+    where
+        T: PreallocatedAccount,
+        F: FnMut(T) + Send + 'static,
+    {
+        let pda = get_pda(&self.program_id, &[owner.as_ref(), seed.as_ref()]);
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            ..RpcAccountInfoConfig::default()
+        };
+        let (subscription, receiver) =
+            PubsubClient::account_subscribe(&self.ws_url, &pda, Some(config))?;
+        std::thread::spawn(move || {
+            while let Ok(response) = receiver.recv() {
+                if let Some(data) = response.value.data.decode() {
+                    if let Ok(value) = T::from_account_data(&data) {
+                        callback(value);
+                    }
+                }
+            }
+        });
+        Ok(AccountSubscription {
+            subscription: Some(subscription),
+        })
+    }
     pub fn initialize_user(
         &self,
         user_keypair: &Keypair,
@@ -133,6 +215,121 @@ impl SocialClient {
         Ok(())
     }
 
+    /// Batches many `FollowUser` instructions into a single `v0` transaction via an Address
+    /// Lookup Table, falling back to one legacy `follow_user` call per entry for a single entry or
+    /// when the lookup table can't be created. If the `v0` transaction still fails after
+    /// `ALT_ACTIVATION_RETRIES` retries, the error is returned without a legacy fallback.
+    pub fn follow_many(
+        &self,
+        user_keypair: &Keypair,
+        follows: &[Pubkey],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if follows.len() <= 1 {
+            for follow_user in follows {
+                self.follow_user(user_keypair, *follow_user)?;
+            }
+            return Ok(());
+        }
+
+        let pda = get_pda(
+            &self.program_id,
+            &[user_keypair.pubkey().as_ref(), USER_PROFILE_SEED.as_ref()],
+        );
+
+        let lookup_table_address =
+            match self.create_lookup_table(user_keypair, &pda, follows) {
+                Ok(address) => address,
+                Err(err) => {
+                    println!("no lookup table available ({err}), falling back to legacy transactions");
+                    for follow_user in follows {
+                        self.follow_user(user_keypair, *follow_user)?;
+                    }
+                    return Ok(());
+                }
+            };
+
+        let follow_instructions: Vec<Instruction> = follows
+            .iter()
+            .map(|follow_user| {
+                Instruction::new_with_borsh(
+                    self.program_id,
+                    &SocialInstruction::FollowUser {
+                        user_to_follow: *follow_user,
+                    },
+                    vec![AccountMeta::new(pda, false)],
+                )
+            })
+            .collect();
+
+        let mut lookup_table_addresses = vec![self.program_id, pda, solana_sdk::system_program::id()];
+        lookup_table_addresses.extend(follows.iter().copied());
+        let lookup_table_account = AddressLookupTableAccount {
+            key: lookup_table_address,
+            addresses: lookup_table_addresses,
+        };
+
+        let last_blockhash = self.rpc_client.get_latest_blockhash()?;
+        let message = MessageV0::try_compile(
+            &user_keypair.pubkey(),
+            &follow_instructions,
+            &[lookup_table_account],
+            last_blockhash,
+        )?;
+        let versioned_transaction =
+            VersionedTransaction::try_new(VersionedMessage::V0(message), &[user_keypair])?;
+
+        // A freshly extended lookup table isn't resolvable until its extending transaction's slot
+        // is in the past, so the first send or two can be rejected even though the table exists.
+        let mut attempt = 0;
+        let signature = loop {
+            match self
+                .rpc_client
+                .send_and_confirm_transaction(&versioned_transaction)
+            {
+                Ok(signature) => break signature,
+                Err(err) if attempt < ALT_ACTIVATION_RETRIES => {
+                    attempt += 1;
+                    println!("lookup table not active yet ({err}), retrying ({attempt}/{ALT_ACTIVATION_RETRIES})");
+                    std::thread::sleep(ALT_ACTIVATION_RETRY_DELAY);
+                }
+                Err(err) => {
+                    return Err(format!(
+                        "follow_many failed after {attempt} retries, possibly because the lookup table isn't active yet: {err}"
+                    )
+                    .into());
+                }
+            }
+        };
+        println!("signature is {:?}", signature);
+        Ok(())
+    }
+
+    /// Creates and extends an Address Lookup Table with the program id, the profile PDA, the
+    /// system program, and every account being followed.
+    fn create_lookup_table(
+        &self,
+        user_keypair: &Keypair,
+        profile_pda: &Pubkey,
+        follows: &[Pubkey],
+    ) -> Result<Pubkey, Box<dyn std::error::Error>> {
+        let recent_slot = self.rpc_client.get_slot()?;
+        let (create_instruction, lookup_table_address) = alt_instruction::create_lookup_table(
+            user_keypair.pubkey(),
+            user_keypair.pubkey(),
+            recent_slot,
+        );
+        let mut new_addresses = vec![self.program_id, *profile_pda, solana_sdk::system_program::id()];
+        new_addresses.extend(follows.iter().copied());
+        let extend_instruction = alt_instruction::extend_lookup_table(
+            lookup_table_address,
+            user_keypair.pubkey(),
+            Some(user_keypair.pubkey()),
+            new_addresses,
+        );
+        self.send_instruction(user_keypair, vec![create_instruction, extend_instruction])?;
+        Ok(lookup_table_address)
+    }
+
     pub fn qurey_followers(
         &self,
         user_keypair: &Keypair,
@@ -149,6 +346,16 @@ impl SocialClient {
         self.send_instruction(user_keypair, vec![query_follower_instruction])?;
         Ok(())
     }
+
+    /// Reads and deserializes the profile account directly, without sending a transaction.
+    pub fn fetch_profile(&self, owner: &Pubkey) -> Result<UserProfile, Box<dyn std::error::Error>> {
+        let pda = get_pda(
+            &self.program_id,
+            &[owner.as_ref(), USER_PROFILE_SEED.as_ref()],
+        );
+        let data = self.rpc_client.get_account_data(&pda)?;
+        UserProfile::from_account_data(&data)
+    }
     pub fn delete_followers(
         &self,
         user_keypair: &Keypair,
@@ -183,6 +390,43 @@ impl SocialClient {
         Ok(())
     }
 
+    /// Reads and deserializes the post account directly, without sending a transaction.
+    pub fn fetch_posts(&self, owner: &Pubkey) -> Result<UserPost, Box<dyn std::error::Error>> {
+        let pda = get_pda(&self.program_id, &[owner.as_ref(), USER_POST_SEED.as_ref()]);
+        let data = self.rpc_client.get_account_data(&pda)?;
+        UserPost::from_account_data(&data)
+    }
+
+    /// Scans every account owned by `program_id` and returns the `owner` of each profile whose
+    /// `follows` contains `target`; non-profile accounts are discarded client-side since there's
+    /// no verified layout to filter on server-side.
+    pub fn find_followers(&self, target: &Pubkey) -> Result<Vec<Pubkey>, Box<dyn std::error::Error>> {
+        let config = RpcProgramAccountsConfig {
+            filters: None,
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = self
+            .rpc_client
+            .get_program_accounts_with_config(&self.program_id, config)?;
+
+        let mut followers = Vec::new();
+        for (_pda, account) in accounts {
+            let profile = match deserialize_profile(&account.data) {
+                Ok(profile) => profile,
+                Err(_) => continue,
+            };
+            if profile.follows.contains(target) {
+                followers.push(profile.owner);
+            }
+        }
+        Ok(followers)
+    }
+
     pub fn send_posts(
         &self,
         user_keypair: &Keypair,
@@ -200,6 +444,45 @@ impl SocialClient {
         self.send_instruction(user_keypair, vec![send_post_instruction])?;
         Ok(())
     }
+
+    pub fn edit_post(
+        &self,
+        user_keypair: &Keypair,
+        index: u64,
+        content: String,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let pda = get_pda(
+            &self.program_id,
+            &[user_keypair.pubkey().as_ref(), USER_POST_SEED.as_ref()],
+        );
+        let edit_post_instruction: Instruction = Instruction::new_with_borsh(
+            self.program_id,
+            &SocialInstruction::EditPost { index, content },
+            vec![AccountMeta::new(pda, false)],
+        );
+        self.send_instruction(user_keypair, vec![edit_post_instruction])?;
+        Ok(())
+    }
+
+    /// Leaves the account larger than the new data; trailing bytes stay zeroed.
+    pub fn delete_post(
+        &self,
+        user_keypair: &Keypair,
+        index: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let pda = get_pda(
+            &self.program_id,
+            &[user_keypair.pubkey().as_ref(), USER_POST_SEED.as_ref()],
+        );
+        let delete_post_instruction: Instruction = Instruction::new_with_borsh(
+            self.program_id,
+            &SocialInstruction::DeletePost { index },
+            vec![AccountMeta::new(pda, false)],
+        );
+        self.send_instruction(user_keypair, vec![delete_post_instruction])?;
+        Ok(())
+    }
+
     pub fn send_instruction(
         &self,
         payer: &Keypair,
@@ -222,6 +505,52 @@ fn get_pda(program_id: &Pubkey, seed: &[&[u8]]) -> Pubkey {
     println!("pda is {}", pda);
     pda
 }
+
+/// Derives the pubsub websocket URL from the JSON-RPC URL, bumping the port by one for local
+/// validators (8899 -> 8900) rather than swapping only the scheme.
+fn derive_ws_url(rpc_url: &str) -> String {
+    let ws_url = rpc_url.replacen("http", "ws", 1);
+    match ws_url.rsplit_once(':') {
+        Some((prefix, port_str)) if port_str.parse::<u16>().is_ok() => {
+            format!("{prefix}:{}", port_str.parse::<u16>().unwrap() + 1)
+        }
+        _ => ws_url,
+    }
+}
+
+/// Slices `data` down to the declared length before deserializing, tolerating the preallocated
+/// account's trailing zero bytes.
+fn deserialize_profile(data: &[u8]) -> Result<UserProfile, Box<dyn std::error::Error>> {
+    if data.len() < 34 {
+        return Err("profile account data shorter than owner + data_len".into());
+    }
+    let data_len = u16::from_le_bytes(data[32..34].try_into()?) as usize;
+    let len = 32 + 2 + 4 + data_len * 32; // owner: Pubkey, data_len: u16, Vec len prefix: u32, Pubkey: 32 bytes each
+    Ok(UserProfile::try_from_slice(&data[..len.min(data.len())])?)
+}
+
+/// Deserializes with Borsh's streaming `deserialize`, which stops after the `Vec<Post>` (sized by
+/// its own length prefix, not by `post_count`), tolerating the preallocated account's trailing zero bytes.
+fn deserialize_posts(mut data: &[u8]) -> Result<UserPost, Box<dyn std::error::Error>> {
+    Ok(UserPost::deserialize(&mut data)?)
+}
+
+/// Account types read from a preallocated on-chain buffer, tolerant of trailing zero bytes.
+trait PreallocatedAccount: Sized {
+    fn from_account_data(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>>;
+}
+
+impl PreallocatedAccount for UserProfile {
+    fn from_account_data(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        deserialize_profile(data)
+    }
+}
+
+impl PreallocatedAccount for UserPost {
+    fn from_account_data(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        deserialize_posts(data)
+    }
+}
 fn main() {
     //calculate_data_size();
     let program_id = "53W1m3utd9wBMAThwa2RR7v4DkXiapbjUG9BUcDkv9WM";